@@ -0,0 +1,760 @@
+//! Declarative, sharded-Parquet vector dataset: enumerate shards from a
+//! filename template, load arbitrary embedding columns by global row range, and
+//! plug into the benchmark [`Dataset`] trait. Concrete corpora (e.g. MS MARCO
+//! v2) are expressed as a [`ParquetVectorDatasetConfig`] preset rather than a
+//! bespoke module.
+
+use std::fs::File;
+use std::io;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arrow::array::{Array, FixedSizeListArray, Float32Array, Float64Array, ListArray};
+use arrow::datatypes::ArrowNativeType;
+use arrow::record_batch::RecordBatch;
+use bytes::Bytes;
+use chroma_distance::DistanceFunction;
+use futures::future::BoxFuture;
+use futures::stream::{FuturesUnordered, Stream, StreamExt};
+use futures::FutureExt;
+use object_store::path::Path as ObjectPath;
+use object_store::ObjectStore;
+use parquet::arrow::arrow_reader::{
+    ArrowReaderOptions, ParquetRecordBatchReaderBuilder, RowSelection, RowSelector,
+};
+use parquet::arrow::async_reader::{AsyncFileReader, ParquetRecordBatchStreamBuilder};
+use parquet::errors::ParquetError;
+use parquet::file::metadata::{ParquetMetaData, ParquetMetaDataReader, RowGroupMetaData};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::{ground_truth, Dataset, Query};
+
+/// Number of shards whose decode may be in flight at once in the async loader.
+/// Keeping this small bounds memory while still letting shard N+1 be opened and
+/// prefetched while shard N is still being decoded.
+const SHARD_PREFETCH_DEPTH: usize = 2;
+
+/// Declarative description of a sharded-Parquet embedding corpus.
+///
+/// A preset fills this in once; new HuggingFace corpora (different models,
+/// dimensions, or column layouts) only need another value of this type.
+#[derive(Clone)]
+pub struct ParquetVectorDatasetConfig {
+    /// Stable dataset name reported through [`Dataset::name`].
+    pub name: &'static str,
+    /// HuggingFace Hub repository id the shards are downloaded from.
+    pub repo_id: &'static str,
+    /// Number of shards in the corpus.
+    pub num_shards: usize,
+    /// Filename template with a single zero-padded index placeholder, e.g.
+    /// `corpus/{:04}.parquet`.
+    pub file_template: &'static str,
+    /// Name of the embedding column within each shard.
+    pub embedding_column: &'static str,
+    /// Embedding dimension.
+    pub dimension: usize,
+    /// Total number of vectors across all shards.
+    pub data_len: usize,
+    /// Path to the precomputed ground-truth Parquet file.
+    pub ground_truth_path: PathBuf,
+}
+
+impl ParquetVectorDatasetConfig {
+    /// Render shard `shard`'s filename from [`file_template`](Self::file_template).
+    pub fn shard_file(&self, shard: usize) -> String {
+        format_shard(self.file_template, shard)
+    }
+}
+
+/// Expand a filename template with a single `{...}` index placeholder. A leading
+/// `:0N` spec zero-pads the index to width `N` (e.g. `corpus/{:04}.parquet` with
+/// index 7 yields `corpus/0007.parquet`).
+fn format_shard(template: &str, index: usize) -> String {
+    let Some(open) = template.find('{') else {
+        return template.to_string();
+    };
+    let Some(rel_close) = template[open..].find('}') else {
+        return template.to_string();
+    };
+    let close = open + rel_close;
+    let spec = &template[open + 1..close];
+    let rendered = if spec.contains('0') {
+        let width = spec
+            .trim_start_matches(':')
+            .trim_start_matches('0')
+            .parse::<usize>()
+            .unwrap_or(0);
+        format!("{index:0width$}")
+    } else {
+        index.to_string()
+    };
+    format!("{}{}{}", &template[..open], rendered, &template[close + 1..])
+}
+
+/// Build the object-store key for shard `shard` under an optional `prefix`.
+fn shard_object_path(config: &ParquetVectorDatasetConfig, prefix: &str, shard: usize) -> ObjectPath {
+    let file = config.shard_file(shard);
+    if prefix.is_empty() {
+        ObjectPath::from(file)
+    } else {
+        ObjectPath::from(format!("{}/{}", prefix.trim_end_matches('/'), file))
+    }
+}
+
+/// An [`AsyncFileReader`] that serves Parquet byte ranges directly from an
+/// [`ObjectStore`], fetching the footer and only the column/page ranges the
+/// reader asks for instead of downloading the whole object.
+struct ObjectStoreReader {
+    store: Arc<dyn ObjectStore>,
+    path: ObjectPath,
+    file_size: Option<usize>,
+}
+
+impl ObjectStoreReader {
+    fn new(store: Arc<dyn ObjectStore>, path: ObjectPath) -> Self {
+        Self {
+            store,
+            path,
+            file_size: None,
+        }
+    }
+}
+
+impl AsyncFileReader for ObjectStoreReader {
+    fn get_bytes(&mut self, range: Range<usize>) -> BoxFuture<'_, parquet::errors::Result<Bytes>> {
+        async move {
+            self.store
+                .get_range(&self.path, range)
+                .await
+                .map_err(|e| ParquetError::External(Box::new(e)))
+        }
+        .boxed()
+    }
+
+    fn get_byte_ranges(
+        &mut self,
+        ranges: Vec<Range<usize>>,
+    ) -> BoxFuture<'_, parquet::errors::Result<Vec<Bytes>>> {
+        async move {
+            self.store
+                .get_ranges(&self.path, &ranges)
+                .await
+                .map_err(|e| ParquetError::External(Box::new(e)))
+        }
+        .boxed()
+    }
+
+    fn get_metadata(&mut self) -> BoxFuture<'_, parquet::errors::Result<Arc<ParquetMetaData>>> {
+        async move {
+            let file_size = match self.file_size {
+                Some(size) => size,
+                None => {
+                    let meta = self
+                        .store
+                        .head(&self.path)
+                        .await
+                        .map_err(|e| ParquetError::External(Box::new(e)))?;
+                    self.file_size = Some(meta.size);
+                    meta.size
+                }
+            };
+            let metadata = ParquetMetaDataReader::new()
+                .with_page_indexes(true)
+                .load_and_finish(self, file_size)
+                .await?;
+            Ok(Arc::new(metadata))
+        }
+        .boxed()
+    }
+}
+
+/// An embedding column, which may be stored as a variable-length list or a
+/// fixed-width list of `f32`/`f64` values.
+enum EmbeddingColumn<'a> {
+    List(&'a ListArray),
+    FixedSize(&'a FixedSizeListArray),
+}
+
+impl<'a> EmbeddingColumn<'a> {
+    fn from_batch(batch: &'a RecordBatch, column: &str) -> io::Result<Self> {
+        let idx = batch
+            .schema()
+            .fields()
+            .iter()
+            .position(|f| f.name() == column)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "column not found"))?;
+
+        let col = batch.column(idx);
+        if let Some(list) = col.as_any().downcast_ref::<ListArray>() {
+            Ok(Self::List(list))
+        } else if let Some(fixed) = col.as_any().downcast_ref::<FixedSizeListArray>() {
+            Ok(Self::FixedSize(fixed))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "column is not a list or fixed-size list",
+            ))
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::List(a) => a.len(),
+            Self::FixedSize(a) => a.len(),
+        }
+    }
+
+    fn is_null(&self, i: usize) -> bool {
+        match self {
+            Self::List(a) => a.is_null(i),
+            Self::FixedSize(a) => a.is_null(i),
+        }
+    }
+
+    fn value(&self, i: usize) -> io::Result<Arc<[f32]>> {
+        match self {
+            Self::List(a) => {
+                let offsets = a.offsets();
+                list_row_to_vec(a.values(), offsets[i].as_usize(), offsets[i + 1].as_usize())
+            }
+            Self::FixedSize(a) => {
+                let width = a.value_length() as usize;
+                let start = i * width;
+                list_row_to_vec(a.values(), start, start + width)
+            }
+        }
+    }
+}
+
+/// Convert one list row's inner float values into an owned `f32` vector,
+/// accepting either `Float32Array` or `Float64Array` element types.
+fn list_row_to_vec(inner: &dyn Array, start: usize, end: usize) -> io::Result<Arc<[f32]>> {
+    if let Some(f32_arr) = inner.as_any().downcast_ref::<Float32Array>() {
+        Ok(Arc::from(&f32_arr.values()[start..end]))
+    } else if let Some(f64_arr) = inner.as_any().downcast_ref::<Float64Array>() {
+        let values: Vec<f32> = f64_arr.values()[start..end]
+            .iter()
+            .map(|&v| v as f32)
+            .collect();
+        Ok(Arc::from(values))
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unsupported array type",
+        ))
+    }
+}
+
+/// Select the row groups that overlap the shard-local interval
+/// `[local_start, local_end)`, returning their indices along with the
+/// shard-local row offset at which the first selected row group begins.
+///
+/// Callers pass the indices to `with_row_groups` and must express any
+/// subsequent [`RowSelection`] relative to the returned start, since the reader
+/// renumbers rows from the first selected group.
+fn overlapping_row_groups(
+    row_groups: &[RowGroupMetaData],
+    local_start: usize,
+    local_end: usize,
+) -> (Vec<usize>, usize) {
+    let mut indices = Vec::new();
+    let mut first_start = 0usize;
+    let mut rg_start = 0usize;
+    for (idx, rg) in row_groups.iter().enumerate() {
+        let rg_end = rg_start + rg.num_rows() as usize;
+        if rg_end > local_start && rg_start < local_end {
+            if indices.is_empty() {
+                first_start = rg_start;
+            }
+            indices.push(idx);
+        }
+        rg_start = rg_end;
+    }
+    (indices, first_start)
+}
+
+/// Build a [`RowSelection`] that skips the first `skip` rows and then selects
+/// the next `select` rows. The reader uses this together with the column offset
+/// index to avoid decompressing pages that hold no selected rows.
+fn page_skip_selection(skip: usize, select: usize) -> RowSelection {
+    let mut selectors = Vec::with_capacity(2);
+    if skip > 0 {
+        selectors.push(RowSelector::skip(skip));
+    }
+    if select > 0 {
+        selectors.push(RowSelector::select(select));
+    }
+    RowSelection::from(selectors)
+}
+
+/// A contiguous, row-group-aligned slice of the corpus assigned to one worker.
+///
+/// Partitions tile `[0, data_len)` exactly once and never split a row group, so
+/// two workers never decode the same row group. The `start_*` fields let a
+/// worker build a reader touching only its assigned files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangePartition {
+    /// Global id of the first vector in this partition.
+    pub global_offset: usize,
+    /// Number of vectors in this partition.
+    pub num_rows: usize,
+    /// Index of the first shard this partition touches.
+    pub start_shard: usize,
+    /// Index, within `start_shard`, of the first row group this partition touches.
+    pub start_row_group: usize,
+    /// Half-open range of shard indices this partition spans.
+    pub shard_range: Range<usize>,
+}
+
+/// Where a [`ParquetVectorDataset`] handle reads its shards from.
+#[derive(Clone)]
+enum Source {
+    /// Shards materialized into the local HuggingFace Hub cache.
+    HfHub { shard_paths: Vec<PathBuf> },
+    /// Shards streamed by byte range from an [`ObjectStore`] under `prefix`.
+    ObjectStore {
+        store: Arc<dyn ObjectStore>,
+        prefix: String,
+    },
+}
+
+/// A reusable sharded-Parquet vector dataset, configured by a
+/// [`ParquetVectorDatasetConfig`].
+pub struct ParquetVectorDataset {
+    config: ParquetVectorDatasetConfig,
+    source: Source,
+}
+
+impl ParquetVectorDataset {
+    /// Load the dataset from HuggingFace Hub.
+    ///
+    /// Requires the ground truth to be precomputed at
+    /// [`config.ground_truth_path`](ParquetVectorDatasetConfig::ground_truth_path).
+    pub async fn load(config: ParquetVectorDatasetConfig) -> io::Result<Self> {
+        // Check ground truth exists before downloading shards.
+        if !ground_truth::exists(&config.ground_truth_path) {
+            return Err(io::Error::other(format!(
+                "Ground truth not found at {}.\n  \
+                 Run: python sphroma/scripts/compute_ground_truth.py --dataset {}",
+                config.ground_truth_path.display(),
+                config.name
+            )));
+        }
+
+        println!("Loading {} from HuggingFace Hub...", config.name);
+
+        let api = hf_hub::api::tokio::Api::new().map_err(io::Error::other)?;
+        let repo = api.dataset(config.repo_id.to_string());
+
+        let mut shard_paths = Vec::with_capacity(config.num_shards);
+        for shard in 0..config.num_shards {
+            let path = repo
+                .get(&config.shard_file(shard))
+                .await
+                .map_err(io::Error::other)?;
+            shard_paths.push(path);
+        }
+
+        Ok(Self {
+            config,
+            source: Source::HfHub { shard_paths },
+        })
+    }
+
+    /// Build a handle that streams shards directly from an [`ObjectStore`]
+    /// (S3, GCS, Azure, or a local/NFS path) under `prefix`, instead of
+    /// materializing the whole corpus into the local HuggingFace Hub cache.
+    ///
+    /// Only the async [`load_range_stream`](Self::load_range_stream) path is
+    /// supported for this source, since shards are fetched by byte range.
+    pub fn from_object_store(
+        config: ParquetVectorDatasetConfig,
+        store: Arc<dyn ObjectStore>,
+        prefix: impl Into<String>,
+    ) -> Self {
+        Self {
+            config,
+            source: Source::ObjectStore {
+                store,
+                prefix: prefix.into(),
+            },
+        }
+    }
+
+    /// The dataset's configuration.
+    pub fn config(&self) -> &ParquetVectorDatasetConfig {
+        &self.config
+    }
+
+    /// Split `[0, data_len)` into up to `num_workers` contiguous partitions
+    /// aligned to Parquet row-group boundaries.
+    ///
+    /// Each partition maps to a whole number of row groups so that no two
+    /// workers ever decode the same row group, and the returned partitions tile
+    /// the corpus exactly once. Row-group row counts are read from each shard's
+    /// footer, so this requires the HfHub source (like
+    /// [`load_range`](Self::load_range)). Each row group goes to the worker
+    /// whose share of the corpus contains its first row, so fewer than
+    /// `num_workers` partitions are returned when the corpus has fewer row
+    /// groups than workers or when a row group spans more than one share.
+    pub fn partitions(&self, num_workers: usize) -> io::Result<Vec<RangePartition>> {
+        let shard_paths = match &self.source {
+            Source::HfHub { shard_paths } => shard_paths,
+            Source::ObjectStore { .. } => {
+                return Err(io::Error::other(
+                    "partitions is only supported for the HfHub source",
+                ));
+            }
+        };
+
+        if num_workers == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Flatten every shard's row groups into a global, ordered list carrying
+        // the running global id offset and owning shard/row-group indices.
+        struct FlatRowGroup {
+            shard: usize,
+            row_group: usize,
+            global_start: usize,
+            num_rows: usize,
+        }
+
+        let mut groups = Vec::new();
+        let mut global = 0usize;
+        for (shard, path) in shard_paths.iter().enumerate() {
+            let file = File::open(path)?;
+            let builder = ParquetRecordBatchReaderBuilder::try_new(file)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            for (row_group, rg) in builder.metadata().row_groups().iter().enumerate() {
+                let num_rows = rg.num_rows() as usize;
+                groups.push(FlatRowGroup {
+                    shard,
+                    row_group,
+                    global_start: global,
+                    num_rows,
+                });
+                global += num_rows;
+            }
+        }
+
+        let total = global;
+        if total == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Assign each row group to a worker by where it starts. The mapping is
+        // monotonic in `global_start`, so groups sharing a worker are contiguous
+        // and can be coalesced into one partition.
+        let mut partitions: Vec<RangePartition> = Vec::new();
+        let mut last_worker = usize::MAX;
+        for g in &groups {
+            let worker = (g.global_start.saturating_mul(num_workers) / total).min(num_workers - 1);
+            if worker != last_worker {
+                partitions.push(RangePartition {
+                    global_offset: g.global_start,
+                    num_rows: 0,
+                    start_shard: g.shard,
+                    start_row_group: g.row_group,
+                    shard_range: g.shard..g.shard + 1,
+                });
+                last_worker = worker;
+            }
+            let partition = partitions.last_mut().expect("partition pushed above");
+            partition.num_rows += g.num_rows;
+            partition.shard_range.end = g.shard + 1;
+        }
+
+        Ok(partitions)
+    }
+
+    /// Load vectors in range [offset, offset+limit).
+    /// Returns (global_id, embedding) pairs.
+    pub fn load_range(&self, offset: usize, limit: usize) -> io::Result<Vec<(u32, Arc<[f32]>)>> {
+        let shard_paths = match &self.source {
+            Source::HfHub { shard_paths } => shard_paths,
+            Source::ObjectStore { .. } => {
+                return Err(io::Error::other(
+                    "synchronous load_range is only supported for the HfHub source; \
+                     use load_range_stream for an ObjectStore source",
+                ));
+            }
+        };
+
+        let end = (offset + limit).min(self.config.data_len);
+        if offset >= end {
+            return Ok(Vec::new());
+        }
+
+        let mut result = Vec::with_capacity(end - offset);
+        let mut shard_start = 0usize;
+
+        for shard_path in shard_paths {
+            if shard_start >= end {
+                break;
+            }
+
+            let file = File::open(shard_path)?;
+            // Load the column offset index so the reader can drop pages that
+            // contain no selected rows without decompressing them.
+            let options = ArrowReaderOptions::new().with_page_index(true);
+            let builder = ParquetRecordBatchReaderBuilder::try_new_with_options(file, options)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let num_rows = builder.metadata().file_metadata().num_rows() as usize;
+            let shard_end = shard_start + num_rows;
+
+            // Skip shards entirely before our range.
+            if shard_end <= offset {
+                shard_start = shard_end;
+                continue;
+            }
+
+            // Translate the requested global range into a local row interval for
+            // this shard. Only the shard straddling `offset` gets a non-zero
+            // local start; the selected run is clamped to this shard's rows so we
+            // never select past `data_len`.
+            let local_start = offset.saturating_sub(shard_start);
+            let local_end = (end - shard_start).min(num_rows);
+
+            // Prune to the row groups overlapping the local interval so the
+            // reader jumps straight to the group containing `offset` and stops
+            // after the last overlapping one. The row selection is expressed
+            // relative to the first selected group, since the reader renumbers
+            // rows from there.
+            let (row_groups, rg_start) =
+                overlapping_row_groups(builder.metadata().row_groups(), local_start, local_end);
+            let select = local_end - local_start;
+            let selection = page_skip_selection(local_start - rg_start, select);
+
+            let reader = builder
+                .with_batch_size(10_000)
+                .with_row_groups(row_groups)
+                .with_row_selection(selection)
+                .build()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            // The reader yields only the selected rows, starting at `local_start`.
+            let mut global_idx = shard_start + local_start;
+            for batch in reader {
+                let batch = batch.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                let column = EmbeddingColumn::from_batch(&batch, self.config.embedding_column)?;
+
+                for i in 0..column.len() {
+                    if column.is_null(i) {
+                        global_idx += 1;
+                        continue;
+                    }
+
+                    result.push((global_idx as u32, column.value(i)?));
+                    global_idx += 1;
+                }
+            }
+
+            shard_start = shard_end;
+        }
+
+        Ok(result)
+    }
+
+    /// Asynchronously stream vectors in range `[offset, offset+limit)` as
+    /// `(global_id, embedding)` pairs.
+    ///
+    /// Unlike [`load_range`](Self::load_range), which opens and decodes each
+    /// shard end-to-end on the calling thread, this path drives shard decode on
+    /// `tokio` via [`ParquetRecordBatchStream`] and prefetches the footer of the
+    /// next shard while the current one is still decoding. This lets ingestion
+    /// benchmarks overlap network, decode, and index insertion instead of
+    /// blocking on each shard in turn.
+    ///
+    /// [`ParquetRecordBatchStream`]: parquet::arrow::async_reader::ParquetRecordBatchStream
+    pub async fn load_range_stream(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> impl Stream<Item = io::Result<(u32, Arc<[f32]>)>> {
+        let (tx, rx) = mpsc::channel(SHARD_PREFETCH_DEPTH);
+        let config = self.config.clone();
+        let source = self.source.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = Self::stream_shards(config, source, offset, limit, &tx).await {
+                // The receiver may already be gone; ignore send failures.
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+
+        ReceiverStream::new(rx)
+    }
+
+    /// Walk the shard list, skipping shards that fall entirely before `offset`,
+    /// and decode each overlapping shard concurrently. A bounded
+    /// [`FuturesUnordered`] keeps at most [`SHARD_PREFETCH_DEPTH`] decode tasks
+    /// in flight so shard N+1 is opened while shard N is still being decoded.
+    async fn stream_shards(
+        config: ParquetVectorDatasetConfig,
+        source: Source,
+        offset: usize,
+        limit: usize,
+        tx: &mpsc::Sender<io::Result<(u32, Arc<[f32]>)>>,
+    ) -> io::Result<()> {
+        let end = (offset + limit).min(config.data_len);
+        if offset >= end {
+            return Ok(());
+        }
+
+        let mut global_start = 0usize;
+        let mut inflight: FuturesUnordered<tokio::task::JoinHandle<io::Result<()>>> =
+            FuturesUnordered::new();
+
+        for shard in 0..config.num_shards {
+            if global_start >= end {
+                break;
+            }
+
+            // Open shard N+1's footer while shard N is still decoding. Each
+            // source produces a builder over a different `AsyncFileReader`, but
+            // the decode path is shared.
+            global_start = match &source {
+                Source::HfHub { shard_paths } => {
+                    let file = tokio::fs::File::open(&shard_paths[shard]).await?;
+                    let builder = ParquetRecordBatchStreamBuilder::new(file)
+                        .await
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    Self::spawn_decode(&config, builder, global_start, offset, end, tx, &mut inflight)
+                        .await?
+                }
+                Source::ObjectStore { store, prefix } => {
+                    let reader = ObjectStoreReader::new(
+                        store.clone(),
+                        shard_object_path(&config, prefix, shard),
+                    );
+                    let builder = ParquetRecordBatchStreamBuilder::new(reader)
+                        .await
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    Self::spawn_decode(&config, builder, global_start, offset, end, tx, &mut inflight)
+                        .await?
+                }
+            };
+        }
+
+        while let Some(res) = inflight.next().await {
+            res.map_err(io::Error::other)??;
+        }
+
+        Ok(())
+    }
+
+    /// Record shard metadata, skip shards before the range, and (bounded by
+    /// [`SHARD_PREFETCH_DEPTH`]) spawn a decode task for an overlapping shard.
+    /// Returns the global row offset of the next shard.
+    async fn spawn_decode<T>(
+        config: &ParquetVectorDatasetConfig,
+        builder: ParquetRecordBatchStreamBuilder<T>,
+        global_start: usize,
+        offset: usize,
+        end: usize,
+        tx: &mpsc::Sender<io::Result<(u32, Arc<[f32]>)>>,
+        inflight: &mut FuturesUnordered<tokio::task::JoinHandle<io::Result<()>>>,
+    ) -> io::Result<usize>
+    where
+        T: AsyncFileReader + Unpin + Send + 'static,
+    {
+        let num_rows = builder.metadata().file_metadata().num_rows() as usize;
+        let shard_start = global_start;
+        let next_start = global_start + num_rows;
+
+        // Skip shards entirely before our range.
+        if shard_start + num_rows <= offset {
+            return Ok(next_start);
+        }
+
+        // Bound the number of concurrent decode tasks.
+        while inflight.len() >= SHARD_PREFETCH_DEPTH {
+            if let Some(res) = inflight.next().await {
+                res.map_err(io::Error::other)??;
+            }
+        }
+
+        let tx = tx.clone();
+        let column = config.embedding_column;
+        inflight.push(tokio::spawn(async move {
+            Self::decode_shard(builder, column, shard_start, offset, end, tx).await
+        }));
+
+        Ok(next_start)
+    }
+
+    /// Decode a single shard, emitting every non-null in-range row to `tx`.
+    async fn decode_shard<T>(
+        builder: ParquetRecordBatchStreamBuilder<T>,
+        column: &'static str,
+        shard_start: usize,
+        offset: usize,
+        end: usize,
+        tx: mpsc::Sender<io::Result<(u32, Arc<[f32]>)>>,
+    ) -> io::Result<()>
+    where
+        T: AsyncFileReader + Unpin + Send + 'static,
+    {
+        let mut stream = builder
+            .with_batch_size(10_000)
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut global_idx = shard_start;
+        while let Some(batch) = stream.next().await {
+            let batch = batch.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let embeddings = EmbeddingColumn::from_batch(&batch, column)?;
+
+            for i in 0..embeddings.len() {
+                if global_idx >= end {
+                    return Ok(());
+                }
+                if embeddings.is_null(i) || global_idx < offset {
+                    global_idx += 1;
+                    continue;
+                }
+
+                // A send error means the consumer stopped reading; bail out.
+                if tx
+                    .send(Ok((global_idx as u32, embeddings.value(i)?)))
+                    .await
+                    .is_err()
+                {
+                    return Ok(());
+                }
+                global_idx += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Dataset for ParquetVectorDataset {
+    fn name(&self) -> &str {
+        self.config.name
+    }
+
+    fn dimension(&self) -> usize {
+        self.config.dimension
+    }
+
+    fn data_len(&self) -> usize {
+        self.config.data_len
+    }
+
+    fn k(&self) -> usize {
+        ground_truth::K
+    }
+
+    fn load_range(&self, offset: usize, limit: usize) -> io::Result<Vec<(u32, Arc<[f32]>)>> {
+        ParquetVectorDataset::load_range(self, offset, limit)
+    }
+
+    fn queries(&self, distance_function: DistanceFunction) -> io::Result<Vec<Query>> {
+        ground_truth::load(&self.config.ground_truth_path, distance_function)
+    }
+}